@@ -1,70 +1,68 @@
+use std::collections::VecDeque;
+use std::io;
 use std::path::PathBuf;
 use clap::Args;
-use y2logs_model::{Log, Level, Pid};
-use chrono::{naive::NaiveDateTime, format::ParseResult};
+use y2logs_model::Log;
+use y2logs_model::format::Format;
 
-pub fn run(args: &FilterArgs) {
-    let log = Log::from_file(&args.file).unwrap();
-    let mut query = log.query();
+use super::query_args::QueryArgs;
 
-    if let Some(level) = &args.level {
-        query.with_level(*level);
-    }
-
-    if let Some(pid) = &args.pid {
-        query.with_pid(*pid);
-    }
-
-    if let Some(component) = &args.component {
-        query.with_component(component.to_owned());
-    }
+pub fn run(args: &FilterArgs) {
+    let mut files = args.files.iter();
+    let first = Log::from_file(files.next().expect("at least one file is required")).unwrap();
+    let rest = files.map(|file| Log::from_file(file).unwrap());
+    let log = first.merge(rest, !args.no_sort);
 
-    if let Some(hostname) = &args.hostname {
-        query.with_hostname(hostname.to_owned());
-    }
+    let mut query = log.query();
+    args.query.apply(&mut query);
 
-    if let Some(datetime) = &args.from_datetime {
-        query.from_datetime(*datetime);
-    }
+    let mut encoder = args.format.encoder();
+    let stdout = io::stdout();
+    let mut handle = stdout.lock();
 
-    if let Some(datetime) = &args.to_datetime {
-        query.to_datetime(*datetime);
+    if let Some(tail) = args.tail {
+        // Bounded ring buffer: memory stays O(tail) regardless of how many entries match.
+        if tail > 0 {
+            let mut ring = VecDeque::with_capacity(tail);
+            for entry in query.iter() {
+                if ring.len() == tail {
+                    ring.pop_front();
+                }
+                ring.push_back(entry);
+            }
+            for entry in ring {
+                encoder.write_entry(&mut handle, entry).unwrap();
+            }
+        }
+    } else {
+        let limit = args.limit.unwrap_or(usize::MAX);
+        for entry in query.iter().take(limit) {
+            encoder.write_entry(&mut handle, entry).unwrap();
+        }
     }
 
-    let filtered = query.to_log();
-
-    for line in filtered {
-        println!("{}", line);
-    }
+    encoder.finish(&mut handle).unwrap();
 }
 
 #[derive(Args, Debug)]
 pub struct FilterArgs {
-    /// YaST2 logs file path
-    pub file: PathBuf,
-    /// Filter by level (debug, info, warn, error, fatal or unknown)
+    /// YaST2 logs file paths, or `-` to read from stdin. When more than one is given,
+    /// they are merged into a single, chronologically sorted stream (see --no-sort).
+    #[clap(required = true)]
+    pub files: Vec<PathBuf>,
+    #[clap(flatten)]
+    pub query: QueryArgs,
+    /// Output format (plain, json, jsonl, logfmt or msgpack)
+    #[clap(long, default_value = "plain")]
+    pub format: Format,
+    /// Stop after this many matches
+    #[clap(long, conflicts_with = "tail")]
+    pub limit: Option<usize>,
+    /// Keep only the last N matches
     #[clap(long)]
-    pub level: Option<Level>,
-    /// Filter by process ID
+    pub tail: Option<usize>,
+    /// Keep entries in the order the files were given instead of sorting them
+    /// chronologically by datetime (the default)
     #[clap(long)]
-    pub pid: Option<Pid>,
-    /// Filter by component name
-    #[clap(long)]
-    pub component: Option<String>,
-    /// Filter by hostname
-    #[clap(long)]
-    pub hostname: Option<String>,
-    /// From the given date/time
-    #[clap(long,value_parser=parse_datetime)]
-    pub from_datetime: Option<NaiveDateTime>,
-    /// Up to the given date/time
-    #[clap(long,value_parser=parse_datetime)]
-    pub to_datetime: Option<NaiveDateTime>
-}
-
-// Parse datetime from the command line
-//
-// TODO: try multiple formats
-fn parse_datetime(s: &str) -> ParseResult<NaiveDateTime> {
-    NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S")
+    pub no_sort: bool,
 }
@@ -1,7 +1,10 @@
 pub mod filter;
+pub mod stats;
+mod query_args;
 
 use clap::{Parser, Subcommand};
 use filter::FilterArgs;
+use stats::StatsArgs;
 
 #[derive(Parser)]
 #[clap(author, version, about, long_about = None)]
@@ -14,6 +17,8 @@ pub struct Cli {
 #[derive(Subcommand, Debug)]
 pub enum Commands {
     /// Filter YaST2 log entries
-    Filter(FilterArgs)
+    Filter(FilterArgs),
+    /// Aggregate YaST2 log entries by a given field
+    Stats(StatsArgs)
 }
 
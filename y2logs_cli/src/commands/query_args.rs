@@ -0,0 +1,203 @@
+use clap::Args;
+use y2logs_model::{Level, Pid, Query};
+use chrono::{naive::{NaiveDate, NaiveDateTime, NaiveTime}, Local};
+use regex::{Regex, RegexBuilder};
+
+/// Options shared by every command that needs to select a subset of log entries.
+///
+/// Does not include the file(s) to read from, since commands differ on how many they
+/// accept (`filter` merges several, `stats` reads a single one).
+#[derive(Args, Debug)]
+pub struct QueryArgs {
+    /// Filter by level (debug, info, warn, error, fatal or unknown)
+    #[clap(long)]
+    pub level: Option<Level>,
+    /// Filter by process ID
+    #[clap(long)]
+    pub pid: Option<Pid>,
+    /// Filter by component name (can be repeated to match any of several components)
+    #[clap(long)]
+    pub component: Vec<String>,
+    /// Filter by hostname
+    #[clap(long)]
+    pub hostname: Option<String>,
+    /// From the given date/time
+    #[clap(long,value_parser=parse_from_datetime)]
+    pub from_datetime: Option<NaiveDateTime>,
+    /// Up to the given date/time
+    #[clap(long,value_parser=parse_to_datetime)]
+    pub to_datetime: Option<NaiveDateTime>,
+    /// Only include entries whose message matches this regular expression
+    #[clap(long, value_parser=parse_grep)]
+    pub grep: Option<Regex>,
+    /// Make --grep case-insensitive
+    #[clap(long)]
+    pub grep_case_insensitive: bool
+}
+
+impl QueryArgs {
+    /// Applies every provided option to the given query.
+    pub fn apply(&self, query: &mut Query) {
+        if let Some(level) = &self.level {
+            query.with_level(*level);
+        }
+
+        if let Some(pid) = &self.pid {
+            query.with_pid(*pid);
+        }
+
+        for component in &self.component {
+            query.with_component(component.to_owned());
+        }
+
+        if let Some(hostname) = &self.hostname {
+            query.with_hostname(hostname.to_owned());
+        }
+
+        if let Some(datetime) = &self.from_datetime {
+            query.from_datetime(*datetime);
+        }
+
+        if let Some(datetime) = &self.to_datetime {
+            query.to_datetime(*datetime);
+        }
+
+        if let Some(regex) = &self.grep {
+            let regex = if self.grep_case_insensitive {
+                // `regex` was already validated by the `--grep` value parser, so
+                // rebuilding the same pattern with a different case sensitivity cannot
+                // fail.
+                RegexBuilder::new(regex.as_str())
+                    .case_insensitive(true)
+                    .build()
+                    .expect("pattern was already validated by the --grep value parser")
+            } else {
+                regex.clone()
+            };
+            query.with_message_regex(regex);
+        }
+    }
+}
+
+// Parse a `--grep` value from the command line
+fn parse_grep(s: &str) -> Result<Regex, String> {
+    Regex::new(s).map_err(|e| format!("Invalid --grep pattern: {}", e))
+}
+
+// Parse a `--from-datetime` value from the command line
+//
+// A bare date (e.g. `2022-08-25`) is assumed to mean the very start of that day.
+fn parse_from_datetime(s: &str) -> Result<NaiveDateTime, String> {
+    parse_datetime(s, NaiveTime::from_hms_opt(0, 0, 0).unwrap())
+}
+
+// Parse a `--to-datetime` value from the command line
+//
+// A bare date (e.g. `2022-08-25`) is assumed to mean the very end of that day, so that
+// `--to-datetime 2022-08-25` includes the whole day rather than excluding it.
+fn parse_to_datetime(s: &str) -> Result<NaiveDateTime, String> {
+    parse_datetime(s, NaiveTime::from_hms_opt(23, 59, 59).unwrap())
+}
+
+// Tries a handful of datetime formats, in order, before giving up.
+//
+// `date_only_time` is the time of day used when `s` only contains a date; it is what
+// makes `--from-datetime`/`--to-datetime` asymmetric for date-only input.
+fn parse_datetime(s: &str, date_only_time: NaiveTime) -> Result<NaiveDateTime, String> {
+    if let Ok(datetime) = NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S") {
+        return Ok(datetime);
+    }
+
+    if let Ok(datetime) = NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M") {
+        return Ok(datetime);
+    }
+
+    if let Ok(date) = NaiveDate::parse_from_str(s, "%Y-%m-%d") {
+        return Ok(NaiveDateTime::new(date, date_only_time));
+    }
+
+    if let Ok(time) = NaiveTime::parse_from_str(s, "%H:%M") {
+        return Ok(NaiveDateTime::new(Local::now().date_naive(), time));
+    }
+
+    if let Ok(time) = NaiveTime::parse_from_str(s, "%H:%M:%S") {
+        return Ok(NaiveDateTime::new(Local::now().date_naive(), time));
+    }
+
+    if let Ok(datetime) = NaiveDateTime::parse_from_str(s, "%d.%m.%Y-%H:%M:%S") {
+        return Ok(datetime);
+    }
+
+    if let Ok(epoch) = s.parse::<i64>() {
+        if let Some(datetime) = NaiveDateTime::from_timestamp_opt(epoch, 0) {
+            return Ok(datetime);
+        }
+    }
+
+    Err(format!(
+        "Could not parse '{}' as a datetime (tried '%Y-%m-%d %H:%M:%S', '%Y-%m-%d %H:%M', \
+         '%Y-%m-%d', '%H:%M', '%H:%M:%S', '%d.%m.%Y-%H:%M:%S' and Unix timestamp)",
+        s
+    ))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_from_datetime_date_only() {
+        let datetime = parse_from_datetime("2022-08-25").unwrap();
+        assert_eq!(
+            datetime,
+            NaiveDate::from_ymd_opt(2022, 8, 25)
+                .unwrap()
+                .and_hms_opt(0, 0, 0)
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_parse_to_datetime_date_only() {
+        let datetime = parse_to_datetime("2022-08-25").unwrap();
+        assert_eq!(
+            datetime,
+            NaiveDate::from_ymd_opt(2022, 8, 25)
+                .unwrap()
+                .and_hms_opt(23, 59, 59)
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_parse_datetime_full() {
+        let datetime = parse_from_datetime("2022-08-25 14:28:44").unwrap();
+        assert_eq!(
+            datetime,
+            NaiveDate::from_ymd_opt(2022, 8, 25)
+                .unwrap()
+                .and_hms_opt(14, 28, 44)
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_parse_datetime_hour_and_minute() {
+        let datetime = parse_from_datetime("14:28").unwrap();
+        assert_eq!(datetime.time(), NaiveTime::from_hms_opt(14, 28, 0).unwrap());
+    }
+
+    #[test]
+    fn test_parse_datetime_epoch() {
+        let datetime = parse_from_datetime("1661437724").unwrap();
+        assert_eq!(
+            datetime,
+            NaiveDateTime::from_timestamp_opt(1661437724, 0).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_parse_datetime_invalid() {
+        assert!(parse_from_datetime("not-a-datetime").is_err());
+    }
+}
@@ -0,0 +1,82 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::str::FromStr;
+use clap::Args;
+use chrono::Timelike;
+use y2logs_model::{Entry, Log};
+
+use super::query_args::QueryArgs;
+
+pub fn run(args: &StatsArgs) {
+    let log = Log::from_file(&args.file).unwrap();
+    let mut query = log.query();
+    args.query.apply(&mut query);
+
+    let filtered = query.to_log();
+
+    let mut counts: HashMap<String, u64> = HashMap::new();
+    for entry in &filtered {
+        *counts.entry(group_key(entry, args.by)).or_insert(0) += 1;
+    }
+
+    let mut rows: Vec<(&String, &u64)> = counts.iter().collect();
+    rows.sort_by(|(key_a, count_a), (key_b, count_b)| count_b.cmp(count_a).then(key_a.cmp(key_b)));
+
+    let total: u64 = counts.values().sum();
+    for (key, count) in rows {
+        println!("{:<30} {}", key, count);
+    }
+    println!("{:<30} {}", "total", total);
+}
+
+// Extracts the grouping key for an entry, according to the chosen `GroupBy`
+fn group_key(entry: &Entry, by: GroupBy) -> String {
+    match by {
+        GroupBy::Level => entry.level.to_string(),
+        GroupBy::Component => entry.component.clone(),
+        GroupBy::Pid => entry.pid.to_string(),
+        GroupBy::Hostname => entry.hostname.clone(),
+        GroupBy::Hour => entry
+            .datetime
+            .date()
+            .and_hms_opt(entry.datetime.hour(), 0, 0)
+            .unwrap()
+            .to_string(),
+    }
+}
+
+#[derive(Args, Debug)]
+pub struct StatsArgs {
+    /// YaST2 logs file path, or `-` to read from stdin
+    pub file: PathBuf,
+    #[clap(flatten)]
+    pub query: QueryArgs,
+    /// Field to group entries by (level, component, pid, hostname or hour)
+    #[clap(long)]
+    pub by: GroupBy,
+}
+
+/// Field used to aggregate entries in the `stats` command
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GroupBy {
+    Level,
+    Component,
+    Pid,
+    Hostname,
+    Hour,
+}
+
+impl FromStr for GroupBy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "level" => Ok(GroupBy::Level),
+            "component" => Ok(GroupBy::Component),
+            "pid" => Ok(GroupBy::Pid),
+            "hostname" => Ok(GroupBy::Hostname),
+            "hour" => Ok(GroupBy::Hour),
+            _ => Err(format!("Could not convert {} into a group-by enum", s)),
+        }
+    }
+}
@@ -1,12 +1,13 @@
 mod commands;
 use clap::Parser;
 use commands::{Cli, Commands};
-use commands::filter;
+use commands::{filter, stats};
 
 fn main() {
     let cli = Cli::parse();
 
     match &cli.command {
-        Commands::Filter(args) => filter::run(args)
+        Commands::Filter(args) => filter::run(args),
+        Commands::Stats(args) => stats::run(args)
     };
 }
@@ -0,0 +1,233 @@
+//! Structured output for `Log`/`Entry` streams.
+//!
+//! The `filter` command used to just `println!` the `Display` impl of `Entry`, which is
+//! lossy and hard to post-process. This module keeps serialization decoupled from
+//! filtering: an `Encoder` knows how to turn entries into bytes, and `Format` picks which
+//! one to use, so the CLI can offer plain text, JSON (one document or one object per
+//! line), logfmt or a compact binary stream without `commands::filter` caring which.
+
+use crate::log::Entry;
+use std::io::{self, Write};
+use std::str::FromStr;
+
+/// Output format selected on the command line.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Format {
+    /// Human readable, one entry per line (the historical behavior).
+    Plain,
+    /// A single JSON array holding every entry.
+    Json,
+    /// One JSON object per entry, one per line; ideal for piping into `jq`.
+    Jsonl,
+    /// `key=value` pairs, one entry per line.
+    Logfmt,
+    /// Length-delimited MessagePack records, for fast re-ingestion.
+    Msgpack,
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "plain" => Ok(Format::Plain),
+            "json" => Ok(Format::Json),
+            "jsonl" => Ok(Format::Jsonl),
+            "logfmt" => Ok(Format::Logfmt),
+            "msgpack" => Ok(Format::Msgpack),
+            _ => Err(format!("Could not convert {} into a format enum", s)),
+        }
+    }
+}
+
+impl Format {
+    /// Builds the encoder matching this format.
+    pub fn encoder(&self) -> Box<dyn Encoder> {
+        match self {
+            Format::Plain => Box::new(PlainEncoder),
+            Format::Json => Box::new(JsonEncoder::default()),
+            Format::Jsonl => Box::new(JsonlEncoder),
+            Format::Logfmt => Box::new(LogfmtEncoder),
+            Format::Msgpack => Box::new(MsgpackEncoder),
+        }
+    }
+}
+
+/// Serializes entries to a writer.
+///
+/// Most encoders write each entry as soon as it arrives. `JsonEncoder` is the
+/// exception: a JSON array needs its closing bracket, so it buffers entries and only
+/// writes them out once `finish` is called.
+pub trait Encoder {
+    /// Writes a single entry.
+    fn write_entry(&mut self, w: &mut dyn Write, entry: &Entry) -> io::Result<()>;
+
+    /// Called once after the last entry has been written.
+    fn finish(&mut self, _w: &mut dyn Write) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Prints entries using their `Display` impl (the original behavior).
+pub struct PlainEncoder;
+
+impl Encoder for PlainEncoder {
+    fn write_entry(&mut self, w: &mut dyn Write, entry: &Entry) -> io::Result<()> {
+        writeln!(w, "{}", entry)
+    }
+}
+
+/// Buffers entries and emits them as a single JSON array on `finish`.
+#[derive(Default)]
+pub struct JsonEncoder {
+    entries: Vec<Entry>,
+}
+
+impl Encoder for JsonEncoder {
+    fn write_entry(&mut self, _w: &mut dyn Write, entry: &Entry) -> io::Result<()> {
+        self.entries.push(entry.clone());
+        Ok(())
+    }
+
+    fn finish(&mut self, w: &mut dyn Write) -> io::Result<()> {
+        serde_json::to_writer_pretty(&mut *w, &self.entries)?;
+        writeln!(w)
+    }
+}
+
+/// Emits one JSON object per entry, one per line.
+pub struct JsonlEncoder;
+
+impl Encoder for JsonlEncoder {
+    fn write_entry(&mut self, w: &mut dyn Write, entry: &Entry) -> io::Result<()> {
+        serde_json::to_writer(&mut *w, entry)?;
+        writeln!(w)
+    }
+}
+
+/// Emits `key=value` pairs, one entry per line.
+pub struct LogfmtEncoder;
+
+impl Encoder for LogfmtEncoder {
+    fn write_entry(&mut self, w: &mut dyn Write, entry: &Entry) -> io::Result<()> {
+        writeln!(
+            w,
+            "datetime=\"{}\" level={} hostname={} pid={} component={} file={} method={} line={} message={:?}",
+            entry.datetime,
+            entry.level.name(),
+            entry.hostname,
+            entry.pid,
+            entry.component,
+            entry.location.file,
+            entry.location.method.as_deref().unwrap_or(""),
+            entry
+                .location
+                .line
+                .map(|line| line.to_string())
+                .unwrap_or_default(),
+            entry.message
+        )
+    }
+}
+
+/// Emits a length-delimited stream of MessagePack-encoded entries.
+///
+/// Each record is prefixed with its length as a 4-byte big-endian integer so a reader
+/// can re-ingest the stream without needing to track message boundaries itself.
+pub struct MsgpackEncoder;
+
+impl Encoder for MsgpackEncoder {
+    fn write_entry(&mut self, w: &mut dyn Write, entry: &Entry) -> io::Result<()> {
+        let bytes =
+            rmp_serde::to_vec(entry).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        w.write_all(&(bytes.len() as u32).to_be_bytes())?;
+        w.write_all(&bytes)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::log::{Level, Location, Pid};
+    use chrono::naive::NaiveDate;
+
+    fn sample_entry() -> Entry {
+        Entry {
+            datetime: NaiveDate::from_ymd_opt(2022, 8, 25)
+                .unwrap()
+                .and_hms_opt(14, 28, 44)
+                .unwrap(),
+            level: Level::Error,
+            hostname: "localhost.localdomain".to_string(),
+            pid: Pid(12375),
+            component: "libstorage".to_string(),
+            location: Location {
+                file: "SystemCmd.cc".to_string(),
+                method: Some("addLine".to_string()),
+                line: Some(569),
+            },
+            message: "Adding Line 14...".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_jsonl_encoder() {
+        let entry = sample_entry();
+        let mut buffer = Vec::new();
+        JsonlEncoder.write_entry(&mut buffer, &entry).unwrap();
+
+        let line = String::from_utf8(buffer).unwrap();
+        assert_eq!(line.matches('\n').count(), 1);
+        let value: serde_json::Value = serde_json::from_str(line.trim_end()).unwrap();
+        assert_eq!(value["level"], "error");
+        assert_eq!(value["pid"], 12375);
+        assert_eq!(value["component"], "libstorage");
+        assert_eq!(value["message"], "Adding Line 14...");
+    }
+
+    #[test]
+    fn test_json_encoder_buffers_until_finish() {
+        let entry = sample_entry();
+        let mut encoder = JsonEncoder::default();
+        let mut buffer = Vec::new();
+
+        // Nothing is written until `finish` is called.
+        encoder.write_entry(&mut buffer, &entry).unwrap();
+        assert!(buffer.is_empty());
+
+        encoder.finish(&mut buffer).unwrap();
+        let value: serde_json::Value = serde_json::from_slice(&buffer).unwrap();
+        assert_eq!(value.as_array().unwrap().len(), 1);
+        assert_eq!(value[0]["level"], "error");
+    }
+
+    #[test]
+    fn test_logfmt_encoder_quotes_datetime_and_message() {
+        let entry = sample_entry();
+        let mut buffer = Vec::new();
+        LogfmtEncoder.write_entry(&mut buffer, &entry).unwrap();
+
+        let line = String::from_utf8(buffer).unwrap();
+        assert_eq!(
+            line,
+            "datetime=\"2022-08-25 14:28:44\" level=error hostname=localhost.localdomain \
+             pid=12375 component=libstorage file=SystemCmd.cc method=addLine line=569 \
+             message=\"Adding Line 14...\"\n"
+        );
+    }
+
+    #[test]
+    fn test_msgpack_encoder_length_prefix() {
+        let entry = sample_entry();
+        let mut buffer = Vec::new();
+        MsgpackEncoder.write_entry(&mut buffer, &entry).unwrap();
+
+        let expected_bytes = rmp_serde::to_vec(&entry).unwrap();
+        let (length_prefix, rest) = buffer.split_at(4);
+        assert_eq!(
+            u32::from_be_bytes(length_prefix.try_into().unwrap()),
+            expected_bytes.len() as u32
+        );
+        assert_eq!(rest, expected_bytes.as_slice());
+    }
+}
@@ -0,0 +1,7 @@
+//! Parses and queries YaST2 log files.
+
+pub mod format;
+pub mod log;
+pub mod parser;
+
+pub use log::{Entry, Level, Location, Log, Pid, Query};
@@ -1,11 +1,14 @@
 // TODO Query only needs the Log struct when executing the query
 
 use chrono::naive::NaiveDateTime;
-use std::{fmt, str::FromStr, path::Path, fs, error::Error};
+use flate2::read::GzDecoder;
+use regex::Regex;
+use std::{collections::HashSet, fmt, str::FromStr, path::Path, fs, io, io::Read, error::Error};
 use crate::parser;
 
 /// Log level of an entry
-#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+#[derive(Debug, PartialEq, Eq, Copy, Clone, serde::Serialize)]
+#[serde(rename_all = "lowercase")]
 pub enum Level {
     Debug = 0,
     Info = 1,
@@ -49,6 +52,21 @@ impl FromStr for Level {
     }
 }
 
+impl Level {
+    /// Lowercase textual name of the level (the same spelling `FromStr` accepts, and
+    /// the one serialized for the JSON-based formats)
+    pub fn name(&self) -> &'static str {
+        match self {
+            Level::Debug => "debug",
+            Level::Info => "info",
+            Level::Warn => "warn",
+            Level::Error => "error",
+            Level::Fatal => "fatal",
+            Level::Unknown => "unknown",
+        }
+    }
+}
+
 impl fmt::Display for Level {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         let value = match self {
@@ -63,7 +81,7 @@ impl fmt::Display for Level {
     }
 }
 
-#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, serde::Serialize)]
 pub struct Pid(pub u32);
 
 impl fmt::Display for Pid {
@@ -86,7 +104,7 @@ impl FromStr for Pid {
 /// Represents the origin of a log entry
 ///
 /// It might include the file, the method and the line (or almost any combination of them).
-#[derive(Debug, PartialEq, Eq, Clone)]
+#[derive(Debug, PartialEq, Eq, Clone, serde::Serialize)]
 pub struct Location {
     /// File name
     pub file: String,
@@ -110,7 +128,7 @@ impl fmt::Display for Location {
 }
 
 /// Represents a log entry
-#[derive(Debug, PartialEq, Eq, Clone)]
+#[derive(Debug, PartialEq, Eq, Clone, serde::Serialize)]
 pub struct Entry {
     /// Entry date and time
     pub datetime: NaiveDateTime,
@@ -152,8 +170,35 @@ pub struct Log {
 
 impl Log {
     /// Constructs a Log struct with the contents of a file
+    ///
+    /// `file_path` can be `-` to read from stdin instead of a real file. Gzip-compressed
+    /// input (e.g. rotated `y2log-1.gz` files) is transparently decompressed.
     pub fn from_file(file_path: &Path) -> Result<Self, Box<dyn Error>> {
-        let contents = fs::read_to_string(file_path)?;
+        let reader: Box<dyn Read> = if file_path == Path::new("-") {
+            Box::new(io::stdin())
+        } else {
+            Box::new(fs::File::open(file_path)?)
+        };
+        Self::from_reader(reader)
+    }
+
+    /// Constructs a Log struct with the contents read from the given reader
+    ///
+    /// Gzip-compressed input is detected by its magic bytes (`0x1f 0x8b`), not its
+    /// extension, so a renamed or piped-in compressed file still works.
+    pub fn from_reader(mut reader: impl Read) -> Result<Self, Box<dyn Error>> {
+        let mut buffer = Vec::new();
+        reader.read_to_end(&mut buffer)?;
+
+        let contents = if buffer.starts_with(&[0x1f, 0x8b]) {
+            let mut decoder = GzDecoder::new(buffer.as_slice());
+            let mut decoded = String::new();
+            decoder.read_to_string(&mut decoded)?;
+            decoded
+        } else {
+            String::from_utf8(buffer)?
+        };
+
         match parser::parse_string(&contents) {
             Ok(entries) => Ok(Log { entries }),
             Err(e) => Err(Box::new(e))
@@ -164,6 +209,25 @@ impl Log {
     pub fn query(&self) -> Query {
         Query::new(self)
     }
+
+    /// Merges this log with others into a single stream, e.g. to reconstruct a
+    /// coherent timeline out of rotated files (`y2log`, `y2log-1`, `y2log-2.gz`, ...)
+    ///
+    /// When `sort` is `true`, the combined entries are stably sorted by `datetime`:
+    /// entries sharing the same timestamp keep the relative order they had (first
+    /// within a file, then across files in the order given), since YaST often writes
+    /// several same-second entries whose order is meaningful.
+    pub fn merge(mut self, others: impl IntoIterator<Item = Log>, sort: bool) -> Self {
+        for other in others {
+            self.entries.extend(other.entries);
+        }
+
+        if sort {
+            self.entries.sort_by_key(|e| e.datetime);
+        }
+
+        self
+    }
 }
 
 // supports "for line in log"
@@ -193,11 +257,12 @@ impl<'a> IntoIterator for &'a Log {
 pub struct  Query<'a> {
     log: &'a Log,
     level: Option<Level>,
-    pid: Option<Pid>,
-    component: Option<String>,
-    hostname: Option<String>,
+    pids: HashSet<Pid>,
+    components: HashSet<String>,
+    hostnames: HashSet<String>,
     from_datetime: Option<NaiveDateTime>,
-    to_datetime: Option<NaiveDateTime>
+    to_datetime: Option<NaiveDateTime>,
+    message_regex: Option<Regex>
 }
 
 impl<'a> Query<'a> {
@@ -206,11 +271,12 @@ impl<'a> Query<'a> {
         Query {
             log,
             level: None,
-            pid: None,
-            component: None,
-            hostname: None,
+            pids: HashSet::new(),
+            components: HashSet::new(),
+            hostnames: HashSet::new(),
             from_datetime: None,
-            to_datetime: None
+            to_datetime: None,
+            message_regex: None
         }
     }
 
@@ -220,21 +286,27 @@ impl<'a> Query<'a> {
         self
     }
 
-    // Adds a condition on the pid field
+    // Adds the given pid to the set of allowed pids
     pub fn with_pid(&mut self, pid: Pid) -> &mut Self {
-        self.pid = Some(pid);
+        self.pids.insert(pid);
         self
     }
 
-    // Adds a condition on the component name field
+    // Adds the given component name to the set of allowed components
     pub fn with_component(&mut self, component: String) -> &mut Self {
-        self.component = Some(component);
+        self.components.insert(component);
         self
     }
 
-    // Adds a condition on the hostname field
+    // Adds the given hostname to the set of allowed hostnames
     pub fn with_hostname(&mut self, hostname: String) -> &mut Self {
-        self.hostname = Some(hostname);
+        self.hostnames.insert(hostname);
+        self
+    }
+
+    // Adds a condition matching the message field against the given regex
+    pub fn with_message_regex(&mut self, regex: Regex) -> &mut Self {
+        self.message_regex = Some(regex);
         self
     }
 
@@ -248,39 +320,48 @@ impl<'a> Query<'a> {
         self
     }
 
+    // Tells whether the given entry satisfies every condition set on this query
+    fn matches(&self, e: &Entry) -> bool {
+        // https://github.com/rust-lang/rfcs/pull/2497
+        if let Some(level) = self.level {
+            if level != e.level { return false };
+        }
+
+        if !self.pids.is_empty() && !self.pids.contains(&e.pid) { return false };
+
+        if !self.components.is_empty() && !self.components.contains(&e.component) { return false };
+
+        if !self.hostnames.is_empty() && !self.hostnames.contains(&e.hostname) { return false };
+
+        if let Some(from_datetime) = &self.from_datetime {
+            if from_datetime > &e.datetime { return false };
+        }
+
+        if let Some(to_datetime) = &self.to_datetime {
+            if to_datetime < &e.datetime { return false };
+        }
+
+        if let Some(regex) = &self.message_regex {
+            if !regex.is_match(&e.message) { return false };
+        }
+
+        true
+    }
+
+    /// Streams the entries matching this query, without materializing a whole `Log`
+    ///
+    /// This is the lazy counterpart of `to_log`: callers that only want the first few
+    /// matches (see `--limit` in the `filter` command) can stop pulling from the
+    /// iterator as soon as they have enough, and callers that want the last few (see
+    /// `--tail`) only need to keep that many around, instead of cloning every match
+    /// up front.
+    pub fn iter(&self) -> impl Iterator<Item = &Entry> {
+        self.log.entries.iter().filter(move |e| self.matches(e))
+    }
+
     // Filters the entries and constructs a new Log object with the result
     pub fn to_log(&self) -> Log {
-        let entries = self.log.entries.iter()
-            .filter(|e| {
-                // https://github.com/rust-lang/rfcs/pull/2497
-                if let Some(level) = self.level {
-                    if level != e.level { return false };
-                }
-
-                if let Some(pid) = self.pid {
-                    if pid != e.pid { return false };
-                }
-
-                if let Some(component) = &self.component {
-                    if component != &e.component { return false };
-                }
-
-                if let Some(hostname) = &self.hostname {
-                    if hostname != &e.hostname { return false };
-                }
-
-                if let Some(from_datetime) = &self.from_datetime {
-                    if from_datetime > &e.datetime { return false };
-                }
-
-                if let Some(to_datetime) = &self.to_datetime {
-                    if to_datetime < &e.datetime { return false };
-                }
-
-                true
-            })
-            .cloned()
-            .collect();
+        let entries = self.iter().cloned().collect();
         Log { entries }
     }
 }